@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 #[serde(deny_unknown_fields)]
 pub struct DebugConfig {
@@ -16,6 +16,11 @@ pub struct DebugConfig {
     pub player_speed: f32,
     /// Number of seconds to leave between frames when rewinding time.
     pub seconds_per_rewind_frame: f32,
+    /// The largest real-time frame delta, in seconds, that will be fed into the
+    /// `GameClock`. Anything larger (a breakpoint, a loading stall, the OS suspending
+    /// the process) is clamped down to this value first, so the game can't take a
+    /// single giant logical time step and have entities jump through walls.
+    pub max_frame_delta: f32,
     /// Enable this when debugging, to save time when rapidly iterating.
     /// It saves you from having to navigate the menu every time you start the game.
     /// If true, the game will open in the editor state.
@@ -25,6 +30,23 @@ pub struct DebugConfig {
     pub display_debug_frames: bool,
 }
 
+impl Default for DebugConfig {
+    fn default() -> Self {
+        DebugConfig {
+            time_scale_presets: Vec::new(),
+            time_scale: 1.0,
+            player_speed: 0.0,
+            seconds_per_rewind_frame: 0.0,
+            // A derived `0.0` default here would clamp every frame's delta down to zero,
+            // freezing `GameClock` (and therefore the whole game) permanently, so the
+            // default is 0.25s instead.
+            max_frame_delta: 0.25,
+            skip_straight_to_editor: false,
+            display_debug_frames: false,
+        }
+    }
+}
+
 impl DebugConfig {
     /// Increase the time scale. Everything in the world will move more quickly.
     /// Return a tuple containing the old scale and the new scale.
@@ -72,4 +94,134 @@ pub struct MovementConfig {
     /// still register. If you start moving sideways later than that, it will not work and the
     /// character will simply jump straight up into the air instead.
     pub jump_allowance: f32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct FixedTimestepConfig {
+    /// How many fixed logic steps to run per second of scaled time.
+    pub hz: f32,
+    /// The most fixed steps that will run in a single frame. Guards against a "spiral
+    /// of death": if a frame falls badly behind, the remainder is dropped instead of
+    /// trying to catch up all at once.
+    pub max_substeps: u32,
+}
+
+impl Default for FixedTimestepConfig {
+    fn default() -> Self {
+        FixedTimestepConfig {
+            hz: 60.0,
+            max_substeps: 8,
+        }
+    }
+}
+
+impl FixedTimestepConfig {
+    /// The duration of a single fixed step, in seconds.
+    pub fn fixed_delta(&self) -> f32 {
+        1.0 / self.hz
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// The logical resolution the game is designed at. The world is always rendered at
+    /// this resolution and then upscaled by `magnification` to fill the window, so pixel
+    /// art is scaled by a whole number instead of being smoothed by arbitrary filtering.
+    pub base_width: u32,
+    pub base_height: u32,
+    /// The values `magnification` can cycle through via the debug actions.
+    pub magnification_presets: Vec<u32>,
+    /// How many window pixels each logical pixel is drawn as.
+    pub magnification: u32,
+    /// The width, in world units, of a single grid tile. Replaces the old magic `100.0`
+    /// sprite scaling constant.
+    pub world_units_per_tile: f32,
+    /// The width, in pixels, of the native sprite art. Replaces the old magic `32.0`
+    /// sprite scaling constant.
+    pub native_sprite_size: f32,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        DisplayConfig {
+            base_width: 640,
+            base_height: 480,
+            magnification_presets: vec![1, 2, 3, 4],
+            magnification: 2,
+            world_units_per_tile: 100.0,
+            native_sprite_size: 32.0,
+        }
+    }
+}
+
+impl DisplayConfig {
+    /// Cycle to the next magnification preset, wrapping back to the first after the last.
+    /// Returns the new magnification.
+    pub fn cycle_magnification(&mut self) -> u32 {
+        let current_index = self
+            .magnification_presets
+            .iter()
+            .position(|&preset| preset == self.magnification);
+        let next_index = match current_index {
+            Some(index) => (index + 1) % self.magnification_presets.len(),
+            None => 0,
+        };
+        if let Some(&next) = self.magnification_presets.get(next_index) {
+            self.magnification = next;
+        }
+        self.magnification
+    }
+
+    /// The factor to scale native sprite art by so it fills one grid tile. This is a
+    /// world-space ratio, independent of `magnification` (which only scales the window's
+    /// logical pixels), so the player sprite stays aligned to the grid at any magnification.
+    pub fn sprite_scale(&self) -> f32 {
+        self.world_units_per_tile / self.native_sprite_size
+    }
+
+    /// The size, in window pixels, that the logical resolution is upscaled to.
+    pub fn scaled_size(&self) -> (u32, u32) {
+        (
+            self.base_width * self.magnification,
+            self.base_height * self.magnification,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display_config() -> DisplayConfig {
+        DisplayConfig {
+            magnification_presets: vec![1, 2, 3, 4],
+            magnification: 2,
+            ..DisplayConfig::default()
+        }
+    }
+
+    #[test]
+    fn cycle_magnification_moves_to_the_next_preset() {
+        let mut config = display_config();
+        assert_eq!(config.cycle_magnification(), 3);
+        assert_eq!(config.magnification, 3);
+    }
+
+    #[test]
+    fn cycle_magnification_wraps_back_to_the_first_preset() {
+        let mut config = display_config();
+        config.magnification = 4;
+        assert_eq!(config.cycle_magnification(), 1);
+    }
+
+    #[test]
+    fn cycle_magnification_starts_from_the_first_preset_if_current_value_is_not_one() {
+        let mut config = display_config();
+        config.magnification = 99;
+        assert_eq!(config.cycle_magnification(), 1);
+    }
 }
\ No newline at end of file
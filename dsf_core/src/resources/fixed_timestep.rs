@@ -0,0 +1,75 @@
+/// Accumulates scaled frame time and doles it out in whole `FixedTimestepConfig::fixed_delta`
+/// steps, so movement and steering integration run at a constant, machine-independent rate
+/// regardless of the display frame rate.
+#[derive(Debug, Default)]
+pub struct FixedTimestepAccumulator {
+    accumulator: f32,
+}
+
+impl FixedTimestepAccumulator {
+    /// Add this frame's scaled delta to the accumulator.
+    pub fn accumulate(&mut self, scaled_delta: f32) {
+        self.accumulator += scaled_delta;
+    }
+
+    /// Consume as many whole `fixed_delta` steps as are available, up to `max_substeps`.
+    /// Returns the number of steps to run this frame. If the guard is hit, the remainder
+    /// is dropped rather than carried forward, so a long pause can't spiral into hundreds
+    /// of catch-up steps next frame.
+    pub fn consume_steps(&mut self, fixed_delta: f32, max_substeps: u32) -> u32 {
+        let mut steps = 0;
+        while self.accumulator >= fixed_delta && steps < max_substeps {
+            self.accumulator -= fixed_delta;
+            steps += 1;
+        }
+        if steps == max_substeps {
+            self.accumulator = 0.0;
+        }
+        steps
+    }
+
+    /// How far we are between the previous and the current fixed step, as a fraction of
+    /// `fixed_delta`. Used to interpolate rendered transforms for smooth visuals at any
+    /// refresh rate.
+    pub fn alpha(&self, fixed_delta: f32) -> f32 {
+        self.accumulator / fixed_delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consume_steps_returns_zero_when_not_enough_time_has_accumulated() {
+        let mut accumulator = FixedTimestepAccumulator::default();
+        accumulator.accumulate(0.01);
+        assert_eq!(accumulator.consume_steps(1.0 / 60.0, 8), 0);
+    }
+
+    #[test]
+    fn consume_steps_runs_one_step_per_whole_fixed_delta() {
+        let fixed_delta = 1.0 / 60.0;
+        let mut accumulator = FixedTimestepAccumulator::default();
+        accumulator.accumulate(fixed_delta * 3.0);
+        assert_eq!(accumulator.consume_steps(fixed_delta, 8), 3);
+    }
+
+    #[test]
+    fn consume_steps_keeps_the_remainder_for_the_next_frame() {
+        let fixed_delta = 1.0 / 60.0;
+        let mut accumulator = FixedTimestepAccumulator::default();
+        accumulator.accumulate(fixed_delta * 1.5);
+        assert_eq!(accumulator.consume_steps(fixed_delta, 8), 1);
+        assert!((accumulator.alpha(fixed_delta) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn consume_steps_drops_the_remainder_once_max_substeps_is_hit() {
+        let fixed_delta = 1.0 / 60.0;
+        let mut accumulator = FixedTimestepAccumulator::default();
+        accumulator.accumulate(fixed_delta * 20.0);
+        assert_eq!(accumulator.consume_steps(fixed_delta, 8), 8);
+        assert_eq!(accumulator.alpha(fixed_delta), 0.0);
+    }
+}
@@ -0,0 +1,11 @@
+mod config;
+mod fixed_timestep;
+mod game_clock;
+mod path_recorder;
+mod rewind;
+
+pub use config::{DebugConfig, DisplayConfig, FixedTimestepConfig, MovementConfig};
+pub use fixed_timestep::FixedTimestepAccumulator;
+pub use game_clock::GameClock;
+pub use path_recorder::{PathPlayback, PathRecorder, PathRecorderError, PathSample};
+pub use rewind::{RewindBuffer, RewindSnapshot};
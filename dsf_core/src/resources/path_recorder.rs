@@ -0,0 +1,166 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+use amethyst::core::math::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::components::DiscretePos;
+
+/// A single timestamped sample of the player's position, taken on the scaled clock.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathSample {
+    /// `GameClock::elapsed()` at the time this sample was taken.
+    pub elapsed: f64,
+    pub discrete_pos: DiscretePos,
+    pub translation: Vector3<f32>,
+}
+
+/// Records the player's path during a run as a stream of `PathSample`s, and can play that
+/// stream back onto a ghost entity. Recordings can be serialized to disk, so a run can be
+/// shipped as a level demo and later raced against.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PathRecorder {
+    samples: Vec<PathSample>,
+}
+
+impl PathRecorder {
+    /// Append a sample, taken on the scaled (`GameClock`) clock.
+    pub fn record(&mut self, elapsed: f64, discrete_pos: DiscretePos, translation: Vector3<f32>) {
+        self.samples.push(PathSample {
+            elapsed,
+            discrete_pos,
+            translation,
+        });
+    }
+
+    pub fn samples(&self) -> &[PathSample] {
+        &self.samples
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> Result<(), PathRecorderError> {
+        let file = File::create(path)?;
+        ron::ser::to_writer_pretty(BufWriter::new(file), self, Default::default())?;
+        Ok(())
+    }
+
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<PathRecorder, PathRecorderError> {
+        let file = File::open(path)?;
+        let recorder = ron::de::from_reader(BufReader::new(file))?;
+        Ok(recorder)
+    }
+}
+
+#[derive(Debug)]
+pub enum PathRecorderError {
+    Io(io::Error),
+    Ron(ron::Error),
+}
+
+impl From<io::Error> for PathRecorderError {
+    fn from(err: io::Error) -> Self {
+        PathRecorderError::Io(err)
+    }
+}
+
+impl From<ron::Error> for PathRecorderError {
+    fn from(err: ron::Error) -> Self {
+        PathRecorderError::Ron(err)
+    }
+}
+
+/// Drives a ghost entity's position along a recorded `PathRecorder` stream.
+///
+/// Stepping is index-plus-elapsed: playback accumulates scaled time and advances the sample index until the next sample's
+/// `elapsed` is in the future, so it automatically honors `DebugConfig::time_scale`
+/// (slow-mo replay, freeze, fast-forward).
+#[derive(Debug)]
+pub struct PathPlayback {
+    samples: Vec<PathSample>,
+    index: usize,
+    /// Scaled time, relative to the start of playback.
+    elapsed: f64,
+}
+
+impl PathPlayback {
+    pub fn new(samples: Vec<PathSample>) -> PathPlayback {
+        PathPlayback {
+            samples,
+            index: 0,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advance playback by `scaled_delta` seconds and return the sample that should
+    /// currently be displayed. Returns `None` only for an empty recording; once the last
+    /// sample is reached it keeps being returned (see `is_finished`).
+    pub fn advance(&mut self, scaled_delta: f32) -> Option<&PathSample> {
+        self.elapsed += f64::from(scaled_delta);
+        while self.index + 1 < self.samples.len() && self.samples[self.index + 1].elapsed <= self.elapsed {
+            self.index += 1;
+        }
+        self.samples.get(self.index)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.index + 1 >= self.samples.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(elapsed: f64, x: f32) -> PathSample {
+        PathSample {
+            elapsed,
+            discrete_pos: DiscretePos::default(),
+            translation: Vector3::new(x, 0.0, 0.0),
+        }
+    }
+
+    #[test]
+    fn advance_stays_on_the_first_sample_until_the_next_ones_time_arrives() {
+        let mut playback = PathPlayback::new(vec![sample(0.0, 0.0), sample(1.0, 1.0)]);
+        let current = playback.advance(0.5).unwrap();
+        assert_eq!(current.translation.x, 0.0);
+    }
+
+    #[test]
+    fn advance_steps_to_the_next_sample_once_its_time_arrives() {
+        let mut playback = PathPlayback::new(vec![sample(0.0, 0.0), sample(1.0, 1.0)]);
+        playback.advance(0.6);
+        let current = playback.advance(0.6).unwrap();
+        assert_eq!(current.translation.x, 1.0);
+    }
+
+    #[test]
+    fn advance_can_skip_several_samples_in_one_call() {
+        let mut playback = PathPlayback::new(vec![
+            sample(0.0, 0.0),
+            sample(1.0, 1.0),
+            sample(2.0, 2.0),
+        ]);
+        let current = playback.advance(2.5).unwrap();
+        assert_eq!(current.translation.x, 2.0);
+    }
+
+    #[test]
+    fn is_finished_once_the_last_sample_is_reached() {
+        let mut playback = PathPlayback::new(vec![sample(0.0, 0.0), sample(1.0, 1.0)]);
+        assert!(!playback.is_finished());
+        playback.advance(5.0);
+        assert!(playback.is_finished());
+    }
+
+    #[test]
+    fn advance_on_an_empty_recording_returns_none() {
+        let mut playback = PathPlayback::new(vec![]);
+        assert!(playback.advance(1.0).is_none());
+        assert!(playback.is_finished());
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+
+use crate::components::{DiscretePos, Steering, Velocity};
+
+/// A single point-in-time capture of the player's state, used to scrub time backward.
+#[derive(Debug, Clone)]
+pub struct RewindSnapshot {
+    pub discrete_pos: DiscretePos,
+    pub translation: amethyst::core::math::Vector3<f32>,
+    pub velocity: Velocity,
+    pub steering: Steering,
+}
+
+/// A ring buffer of `RewindSnapshot`s, recorded every `DebugConfig::seconds_per_rewind_frame`
+/// of scaled time while the player is in normal play.
+///
+/// While `RewindingState` is active, snapshots are popped off the back (most recent first)
+/// and written onto the player's components, walking backward through the recording. Once
+/// playback resumes, any snapshots newer than the rewound position are dropped, so recording
+/// doesn't fork into a stale future.
+#[derive(Debug)]
+pub struct RewindBuffer {
+    snapshots: VecDeque<RewindSnapshot>,
+    capacity: usize,
+    /// Scaled-time `GameClock::elapsed()` at which the next snapshot is due.
+    next_record_at: f64,
+}
+
+impl RewindBuffer {
+    pub fn new(capacity: usize) -> RewindBuffer {
+        RewindBuffer {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+            next_record_at: 0.0,
+        }
+    }
+
+    /// Whether `elapsed` (scaled-time `GameClock::elapsed()`) has reached the point where
+    /// another snapshot should be recorded.
+    pub fn is_due(&self, elapsed: f64) -> bool {
+        elapsed >= self.next_record_at
+    }
+
+    /// Append a snapshot, dropping the oldest one if the buffer is already full, and
+    /// schedule the next recording `interval` scaled-seconds later.
+    pub fn record(&mut self, snapshot: RewindSnapshot, elapsed: f64, interval: f32) {
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+        self.next_record_at = elapsed + f64::from(interval);
+    }
+
+    /// Push the next recording out by `interval` scaled-seconds from `elapsed`, without
+    /// recording a snapshot. Called when resuming from a rewind, so the recorder doesn't
+    /// immediately overwrite the position the player just rewound to.
+    pub fn reseed(&mut self, elapsed: f64, interval: f32) {
+        self.next_record_at = elapsed + f64::from(interval);
+    }
+
+    /// Pop the most recent snapshot off the buffer, if any. Does nothing when the buffer
+    /// is empty, leaving the player at whatever position it already occupies.
+    pub fn rewind_one_frame(&mut self) -> Option<RewindSnapshot> {
+        self.snapshots.pop_back()
+    }
+
+    /// Whether there is anything left to rewind into.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+}
+
+impl Default for RewindBuffer {
+    /// A reasonably generous default capacity; callers that care should construct with
+    /// `RewindBuffer::new` instead.
+    fn default() -> Self {
+        RewindBuffer::new(600)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::core::math::Vector3;
+
+    fn snapshot() -> RewindSnapshot {
+        let discrete_pos = DiscretePos::default();
+        RewindSnapshot {
+            discrete_pos,
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            velocity: Velocity::default(),
+            steering: Steering::new(discrete_pos),
+        }
+    }
+
+    #[test]
+    fn is_due_before_any_recording_is_true_at_zero() {
+        let buffer = RewindBuffer::new(4);
+        assert!(buffer.is_due(0.0));
+    }
+
+    #[test]
+    fn record_schedules_the_next_recording_after_interval() {
+        let mut buffer = RewindBuffer::new(4);
+        buffer.record(snapshot(), 1.0, 0.5);
+        assert!(!buffer.is_due(1.2));
+        assert!(buffer.is_due(1.5));
+    }
+
+    #[test]
+    fn record_evicts_the_oldest_snapshot_once_full() {
+        let mut buffer = RewindBuffer::new(2);
+        for x in 0..3 {
+            let snapshot = RewindSnapshot {
+                translation: Vector3::new(x as f32, 0.0, 0.0),
+                ..snapshot()
+            };
+            buffer.record(snapshot, f64::from(x), 1.0);
+        }
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.rewind_one_frame().unwrap().translation.x, 2.0);
+        assert_eq!(buffer.rewind_one_frame().unwrap().translation.x, 1.0);
+        assert!(buffer.rewind_one_frame().is_none());
+    }
+
+    #[test]
+    fn rewind_one_frame_pops_most_recently_recorded_first() {
+        let mut buffer = RewindBuffer::new(4);
+        for x in 0..2 {
+            let snapshot = RewindSnapshot {
+                translation: Vector3::new(x as f32, 0.0, 0.0),
+                ..snapshot()
+            };
+            buffer.record(snapshot, f64::from(x), 1.0);
+        }
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.rewind_one_frame().unwrap().translation.x, 1.0);
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(buffer.rewind_one_frame().unwrap().translation.x, 0.0);
+        assert!(buffer.is_empty());
+        assert!(buffer.rewind_one_frame().is_none());
+    }
+
+    #[test]
+    fn reseed_pushes_back_the_next_recording_without_recording() {
+        let mut buffer = RewindBuffer::new(4);
+        buffer.reseed(1.0, 0.5);
+        assert_eq!(buffer.len(), 0);
+        assert!(!buffer.is_due(1.2));
+        assert!(buffer.is_due(1.5));
+    }
+}
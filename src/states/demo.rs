@@ -9,21 +9,28 @@ use amethyst::{
     prelude::Builder,
     renderer::{Camera, sprite::SpriteRender},
     StateData,
-    Trans, window::ScreenDimensions,
+    Trans,
 };
 use amethyst::core::math::Vector3;
 use amethyst::prelude::WorldExt;
 use amethyst::State;
 use amethyst::StateEvent;
 use amethyst::ui::UiPrefab;
+use amethyst::window::Window;
+use amethyst::winit::dpi::LogicalSize;
 use log::info;
 use precompile::AnimationId;
 use precompile::MyPrefabData;
 
+use amethyst::core::Time;
+
 use crate::components::*;
 use crate::game_data::CustomGameData;
-use crate::resources::setup_debug_lines;
-use crate::states::PausedState;
+use crate::resources::{
+    setup_debug_lines, FixedTimestepAccumulator, FixedTimestepConfig, GameClock, PathPlayback,
+    PathRecorder, RewindBuffer, RewindSnapshot,
+};
+use crate::states::{PausedState, RewindingState};
 use crate::prefabs::Prefabs;
 use crate::config::*;
 
@@ -32,6 +39,27 @@ pub struct DemoState {
     prefabs: Prefabs,
     fps_ui: Handle<UiPrefab>,
     paused_ui: Handle<UiPrefab>,
+    player: Option<Entity>,
+    /// A child of `player`, carrying the sprite prefab and the grid-to-world scale.
+    /// Interpolation is drawn onto this entity's local `Transform` instead of the
+    /// player's, so the authoritative, simulated `Transform` on `player` is never
+    /// touched for cosmetic purposes and stays fit to read back as "previous" next step.
+    player_visual: Option<Entity>,
+    /// The player's authoritative `Transform` as of just before the most recent frame's
+    /// *last* fixed step ran, kept around so the current frame can be interpolated
+    /// between it and the new one for smooth visuals at any display refresh rate. Only
+    /// updated on frames where at least one fixed step actually runs, so it stays valid
+    /// (and the interpolation stays smooth, not stale) on the many frames where none
+    /// does; and only the last step, so a frame that catches up more than one step
+    /// doesn't interpolate across all of them at once.
+    prev_player_transform: Option<Transform>,
+    /// A dedicated ghost entity driven only by `ghost_playback`, separate from the
+    /// `DebugPosGhostTag` entity (which is mirrored onto the live player by an existing
+    /// debug system every gameplay dispatch). Reusing that tagged entity for playback
+    /// would have it snapped back onto the live player every stepping frame.
+    path_ghost: Option<Entity>,
+    /// Set while a recorded path is being replayed onto `path_ghost`.
+    ghost_playback: Option<PathPlayback>,
 }
 
 impl<'a, 'b> DemoState {
@@ -44,36 +72,188 @@ impl<'a, 'b> DemoState {
             prefabs,
             fps_ui,
             paused_ui,
+            player: None,
+            player_visual: None,
+            prev_player_transform: None,
+            path_ghost: None,
+            ghost_playback: None,
         }
     }
 
     fn handle_action(&mut self, action: &str, world: &mut World) -> Trans<CustomGameData<'a, 'b>, StateEvent> {
-        let mut config = world.fetch_mut::<DebugConfig>();
         if action == "speedUp" {
+            let mut config = world.fetch_mut::<DebugConfig>();
             let (old_speed, new_speed) = (*config).increase_speed();
             println!("Speeding up, from {:?} to {:?}", old_speed, new_speed);
             Trans::None
         } else if action == "slowDown" {
+            let mut config = world.fetch_mut::<DebugConfig>();
             let (old_speed, new_speed) = (*config).decrease_speed();
             println!("Slowing down, from {:?} to {:?}", old_speed, new_speed);
             Trans::None
+        } else if action == "rewind" {
+            // Push the rewind state; it pops back out as soon as the key is released.
+            Trans::Push(Box::new(RewindingState::new(self.player.unwrap())))
+        } else if action == "replay" {
+            let samples = world.fetch::<PathRecorder>().samples().to_vec();
+            if !samples.is_empty() {
+                self.ghost_playback = Some(PathPlayback::new(samples));
+            }
+            Trans::None
+        } else if action == "cycleMagnification" {
+            let new_magnification = world.fetch_mut::<DisplayConfig>().cycle_magnification();
+            println!("Cycling display magnification to {:?}", new_magnification);
+            initialise_camera(world);
+            Trans::None
         } else {
             Trans::None
         }
     }
+
+    /// Append the player's current position to the `PathRecorder`, sampled on the same
+    /// scaled clock as the rest of the game.
+    fn record_path_sample(&self, world: &World) {
+        let player = match self.player {
+            Some(player) => player,
+            None => return,
+        };
+        let elapsed = world.fetch::<GameClock>().elapsed();
+        let positions = world.read_storage::<DiscretePos>();
+        let transforms = world.read_storage::<Transform>();
+        if let (Some(&discrete_pos), Some(transform)) =
+            (positions.get(player), transforms.get(player))
+        {
+            world.fetch_mut::<PathRecorder>().record(
+                elapsed,
+                discrete_pos,
+                *transform.translation(),
+            );
+        }
+    }
+
+    /// Advance any in-progress ghost replay by `scaled_delta` and move `path_ghost` to the
+    /// current sample, so playback honors `DebugConfig::time_scale` (slow-mo, freeze, etc.).
+    fn advance_ghost_playback(&mut self, world: &World, scaled_delta: f32) {
+        let path_ghost = match self.path_ghost {
+            Some(path_ghost) => path_ghost,
+            None => return,
+        };
+        let sample = match &mut self.ghost_playback {
+            Some(playback) => playback.advance(scaled_delta).cloned(),
+            None => return,
+        };
+        if let Some(sample) = sample {
+            if let Some(transform) = world.write_storage::<Transform>().get_mut(path_ghost) {
+                transform.set_translation(sample.translation);
+            }
+        }
+        if self
+            .ghost_playback
+            .as_ref()
+            .map_or(false, PathPlayback::is_finished)
+        {
+            self.ghost_playback = None;
+        }
+    }
+
+    /// Append a snapshot of the player to the `RewindBuffer`, once every
+    /// `DebugConfig::seconds_per_rewind_frame` of scaled time.
+    fn record_rewind_frame(&self, world: &World) {
+        let player = match self.player {
+            Some(player) => player,
+            None => return,
+        };
+        let elapsed = world.fetch::<GameClock>().elapsed();
+        let mut rewind_buffer = world.fetch_mut::<RewindBuffer>();
+        if !rewind_buffer.is_due(elapsed) {
+            return;
+        }
+        let interval = world.fetch::<DebugConfig>().seconds_per_rewind_frame;
+        let positions = world.read_storage::<DiscretePos>();
+        let transforms = world.read_storage::<Transform>();
+        let velocities = world.read_storage::<Velocity>();
+        let steerings = world.read_storage::<Steering>();
+        if let (Some(&discrete_pos), Some(transform), Some(velocity), Some(steering)) = (
+            positions.get(player),
+            transforms.get(player),
+            velocities.get(player),
+            steerings.get(player),
+        ) {
+            rewind_buffer.record(
+                RewindSnapshot {
+                    discrete_pos,
+                    translation: *transform.translation(),
+                    velocity: velocity.clone(),
+                    steering: steering.clone(),
+                },
+                elapsed,
+                interval,
+            );
+        }
+    }
+
+    /// Snapshot the player's `Transform` before a fixed-timestep substep advances it, so
+    /// it can be interpolated against afterwards for smooth rendering.
+    fn store_prev_player_transform(&mut self, world: &World) {
+        if let Some(player) = self.player {
+            if let Some(transform) = world.read_storage::<Transform>().get(player) {
+                self.prev_player_transform = Some(transform.clone());
+            }
+        }
+    }
+
+    /// Blend the player's rendered position between its authoritative `Transform`'s value
+    /// before the most recent fixed step and its value after, by `alpha`, so rendering
+    /// stays smooth even though logic only advances in whole
+    /// `FixedTimestepConfig::fixed_delta` increments.
+    ///
+    /// The blend is written to `player_visual`'s local `Transform` as an offset from the
+    /// player, never to the player's own `Transform`: that stays exactly equal to the
+    /// simulated position, so next step's integration (and the next frame's "previous"
+    /// snapshot) is never contaminated by a cosmetic lerp.
+    fn interpolate_player_transform(&self, world: &World, alpha: f32) {
+        let player = match self.player {
+            Some(player) => player,
+            None => return,
+        };
+        let visual = match self.player_visual {
+            Some(visual) => visual,
+            None => return,
+        };
+        let prev = match &self.prev_player_transform {
+            Some(prev) => prev,
+            None => return,
+        };
+        let current_translation = {
+            let transforms = world.read_storage::<Transform>();
+            match transforms.get(player) {
+                Some(transform) => *transform.translation(),
+                None => return,
+            }
+        };
+        let alpha = alpha.clamp(0.0, 1.0);
+        let interpolated = prev.translation().lerp(&current_translation, alpha);
+        let offset = interpolated - current_translation;
+        let mut transforms = world.write_storage::<Transform>();
+        if let Some(visual_transform) = transforms.get_mut(visual) {
+            visual_transform.set_translation(offset);
+        }
+    }
 }
 
 impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for DemoState {
     fn on_start(&mut self, data: StateData<'_, CustomGameData<'_, '_>>) {
         let StateData { world, .. } = data;
+        // Unlike GameClock/RewindBuffer/etc. below, DisplayConfig isn't inserted here:
+        // it's loaded from the game config and put in the World the same way
+        // DebugConfig is, before this state ever starts. Force-inserting a default
+        // here would silently discard whatever base_width/magnification_presets/etc.
+        // were configured on disk.
         let discrete_pos = DiscretePos::default();
         let mut transform = Transform::default();
         transform.set_translation_xyz((discrete_pos.x * 50 + 50) as f32, (discrete_pos.x * 50 + 50) as f32, 0.0);
-        let scale_factor = 100.0 / 32.0;
-        transform.set_scale(Vector3::new(scale_factor, scale_factor, 1.0));
         let player = world
             .create_entity()
-            .with(self.prefabs.get_mob())
             .with(transform)
             .with(discrete_pos)
             .with(Velocity::default())
@@ -81,6 +261,19 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for DemoState {
             .with(PlayerTag)
             .build();
 
+        // The sprite and its grid scale live on a child entity, never on `player`
+        // itself, so rendering (including interpolation, see `interpolate_player_transform`)
+        // never has to touch the authoritative, simulated `Transform`.
+        let scale_factor = world.fetch::<DisplayConfig>().sprite_scale();
+        let mut visual_transform = Transform::default();
+        visual_transform.set_scale(Vector3::new(scale_factor, scale_factor, 1.0));
+        let player_visual = world
+            .create_entity()
+            .with(self.prefabs.get_mob())
+            .with(visual_transform)
+            .with(Parent { entity: player })
+            .build();
+
         let mut ghost_transform = Transform::default();
         ghost_transform.set_scale(Vector3::new(2.0, 2.0, 1.0));
         world
@@ -89,14 +282,32 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for DemoState {
             .with(ghost_transform)
             .with(DebugSteeringGhostTag)
             .build();
+        // Mirrored onto the live player every gameplay dispatch by an existing debug
+        // system; not stored on self since path playback below uses its own entity.
         world
             .create_entity()
             .with(self.prefabs.get_frame())
             .with(Transform::default())
             .with(DebugPosGhostTag)
             .build();
+        // A separate, untagged entity for path playback, so it isn't also caught by
+        // the DebugPosGhostTag mirror above and snapped back onto the live player
+        // every frame.
+        let path_ghost = world
+            .create_entity()
+            .with(self.prefabs.get_frame())
+            .with(Transform::default())
+            .build();
         initialise_camera(world);
         setup_debug_lines(world);
+        world.insert(GameClock::default());
+        world.insert(RewindBuffer::default());
+        world.insert(FixedTimestepConfig::default());
+        world.insert(FixedTimestepAccumulator::default());
+        world.insert(PathRecorder::default());
+        self.player = Some(player);
+        self.player_visual = Some(player_visual);
+        self.path_ghost = Some(path_ghost);
     }
 
     fn update(
@@ -104,6 +315,16 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for DemoState {
         data: StateData<'_, CustomGameData<'_, '_>>,
     ) -> Trans<CustomGameData<'a, 'b>, StateEvent> {
         let StateData { world, .. } = data;
+        let (time_scale, scaled_delta, raw_delta) = {
+            let raw_delta = world.fetch::<Time>().delta_seconds();
+            let debug_config = world.fetch::<DebugConfig>();
+            let mut game_clock = world.fetch_mut::<GameClock>();
+            game_clock.tick(raw_delta, &debug_config);
+            (debug_config.time_scale, game_clock.scaled_delta(), raw_delta)
+        };
+        self.record_rewind_frame(world);
+        self.record_path_sample(world);
+        self.advance_ghost_playback(world, scaled_delta);
         // Execute a pass similar to a system
         world.exec(
             |(entities, animation_sets, mut control_sets): (
@@ -115,18 +336,60 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for DemoState {
                 for (entity, animation_set) in (&entities, &animation_sets).join() {
                     // Creates a new AnimationControlSet for the entity
                     let control_set = get_animation_set(&mut control_sets, entity).unwrap();
-                    // Adds the `Fly` animation to AnimationControlSet and loops infinitely
+                    // Adds the `Fly` animation to AnimationControlSet and loops infinitely,
+                    // at a rate driven by the scaled clock so it speeds up, slows down or
+                    // freezes along with the rest of the world.
                     control_set.add_animation(
                         AnimationId::Fly,
                         &animation_set.get(&AnimationId::Fly).unwrap(),
                         EndControl::Loop(None),
-                        1.0,
+                        time_scale,
                         AnimationCommand::Start,
                     );
+                    // `add_animation` does nothing once `Fly` is already present, so the
+                    // rate has to be refreshed explicitly to follow later time_scale changes.
+                    control_set.set_rate(AnimationId::Fly, time_scale);
                 }
             },
         );
-        data.data.update(&world, true);
+        let (fixed_delta, max_substeps) = {
+            let config = world.fetch::<FixedTimestepConfig>();
+            (config.fixed_delta(), config.max_substeps)
+        };
+        let (steps, alpha) = {
+            let mut accumulator = world.fetch_mut::<FixedTimestepAccumulator>();
+            accumulator.accumulate(scaled_delta);
+            let steps = accumulator.consume_steps(fixed_delta, max_substeps);
+            (steps, accumulator.alpha(fixed_delta))
+        };
+        if steps > 0 {
+            // Dispatch gameplay systems with `Time` overridden to the fixed step's
+            // duration, not the raw, frame-rate-dependent delta it otherwise reports.
+            // `scaled_delta` already decided how many steps run this frame (more of
+            // them when sped up, zero while frozen), so overriding each step's size
+            // to the constant `fixed_delta` here doesn't lose that scaling -- it's
+            // what keeps each individual step deterministic.
+            world.fetch_mut::<Time>().set_delta_seconds(fixed_delta);
+            for _ in 0..(steps - 1) {
+                data.data.update(&world, true);
+            }
+            // `alpha` below only ever covers the single fixed_delta since the *last*
+            // substep, so "previous" must be captured right before that last substep,
+            // not before the whole batch: on a frame that catches up more than one
+            // step (e.g. after a stall), capturing any earlier would leave `prev`
+            // several fixed_deltas behind `current`, and the player would visibly
+            // snap back towards it before easing forward again.
+            self.store_prev_player_transform(world);
+            data.data.update(&world, true);
+            world.fetch_mut::<Time>().set_delta_seconds(raw_delta);
+        } else {
+            // No fixed step was due this frame, which is the common case at display
+            // refresh rates above `FixedTimestepConfig::hz`. Still dispatch the
+            // non-gameplay systems (`false`) so rendering keeps pace with the display;
+            // gameplay is skipped so nothing integrates twice.
+            data.data.update(&world, false);
+        }
+        self.interpolate_player_transform(world, alpha);
         Trans::None
     }
 
@@ -166,12 +429,33 @@ impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for DemoState {
     }
 }
 
-/// Initialise the camera.
+/// Initialise the camera over the fixed logical resolution (`DisplayConfig::base_width`/
+/// `base_height`), never `scaled_size()`: the camera's world extent must not change when
+/// `magnification` is cycled, or cycling it would zoom the world in and out instead of
+/// upscaling it. The window itself is resized to `scaled_size()` below, so the same,
+/// unchanged logical view is simply presented at a bigger (or smaller) integer multiple
+/// of window pixels. Can be called again at runtime (e.g. after cycling the
+/// magnification) as it first tears down any camera it previously created.
 fn initialise_camera(world: &mut World) {
-    let (width, height) = {
-        let dim = world.fetch::<ScreenDimensions>();
-        (dim.width(), dim.height())
+    despawn_camera(world);
+
+    let (width, height, scaled_width, scaled_height) = {
+        let display_config = world.fetch::<DisplayConfig>();
+        let (scaled_width, scaled_height) = display_config.scaled_size();
+        (
+            display_config.base_width,
+            display_config.base_height,
+            scaled_width,
+            scaled_height,
+        )
     };
+    // Resize the window to the magnified size. The camera below keeps covering the
+    // fixed logical resolution, so the renderer fills this bigger (or smaller) window
+    // with that same view, i.e. upscales it by a whole number of window pixels.
+    world
+        .fetch::<Window>()
+        .set_inner_size(LogicalSize::new(f64::from(scaled_width), f64::from(scaled_height)));
+
     // Setup camera in a way that our screen covers whole arena and (0, 0) is in the bottom left.
     let mut transform = Transform::default();
     transform.set_translation_xyz(0.0, 0.0, 1.0);
@@ -187,7 +471,30 @@ fn initialise_camera(world: &mut World) {
         .with(Parent {
             entity: camera_frame,
         })
-        .with(Camera::standard_2d(width, height))
+        .with(Camera::standard_2d(width as f32, height as f32))
         .with(Transform::default())
         .build();
 }
+
+/// Remove any existing camera frame and its child camera entity, so `initialise_camera`
+/// can be safely called more than once, e.g. to pick up a new `DisplayConfig::magnification`.
+///
+/// Both entities are deleted explicitly, rather than relying on `Parent`-cascading
+/// deletion, which only runs as part of the next dispatch: left to itself, the old
+/// `Camera` entity would still be alive when the new one is created a few lines below,
+/// leaving two cameras active at once.
+fn despawn_camera(world: &mut World) {
+    let stale_entities: Vec<Entity> = {
+        let entities = world.entities();
+        let camera_frame_tags = world.read_storage::<CameraFrameTag>();
+        let cameras = world.read_storage::<Camera>();
+        (&entities, &camera_frame_tags)
+            .join()
+            .map(|(entity, _)| entity)
+            .chain((&entities, &cameras).join().map(|(entity, _)| entity))
+            .collect()
+    };
+    world
+        .delete_entities(&stale_entities)
+        .expect("Failed to delete previous camera entities");
+}
@@ -0,0 +1,107 @@
+use amethyst::core::{transform::Transform, Time};
+use amethyst::ecs::{prelude::World, WriteStorage};
+use amethyst::input::{InputHandler, StringBindings};
+use amethyst::prelude::WorldExt;
+use amethyst::{Entity, State, StateData, StateEvent, Trans};
+
+use crate::components::*;
+use crate::game_data::CustomGameData;
+use crate::resources::{DebugConfig, GameClock, RewindBuffer};
+
+/// A pushed state (mirroring `PausedState`) entered while the player holds the rewind key.
+///
+/// It pops the most recent snapshots off the `RewindBuffer` and writes them back onto the
+/// player's components, stepping backward through the recording at
+/// `1 / seconds_per_rewind_frame` frames per real second. Stepping is driven by an
+/// elapsed-time accumulator, so a single engine frame may consume zero, one or several
+/// snapshots, and rewind speed doesn't depend on the display frame rate.
+pub struct RewindingState {
+    player: Entity,
+    /// Real seconds accumulated since the last snapshot was consumed.
+    elapsed_since_last_step: f32,
+}
+
+impl RewindingState {
+    pub fn new(player: Entity) -> RewindingState {
+        RewindingState {
+            player,
+            elapsed_since_last_step: 0.0,
+        }
+    }
+
+    /// Pop the most recent snapshot off the `RewindBuffer` and write it onto the
+    /// player's components. Returns whether a snapshot was available.
+    fn apply_next_rewind_step(&self, world: &World) -> bool {
+        let snapshot = world.fetch_mut::<RewindBuffer>().rewind_one_frame();
+        let snapshot = match snapshot {
+            Some(snapshot) => snapshot,
+            None => return false,
+        };
+        let (mut positions, mut transforms, mut velocities, mut steerings): (
+            WriteStorage<DiscretePos>,
+            WriteStorage<Transform>,
+            WriteStorage<Velocity>,
+            WriteStorage<Steering>,
+        ) = world.system_data();
+        if let Some(pos) = positions.get_mut(self.player) {
+            *pos = snapshot.discrete_pos;
+        }
+        if let Some(transform) = transforms.get_mut(self.player) {
+            transform.set_translation(snapshot.translation);
+        }
+        if let Some(velocity) = velocities.get_mut(self.player) {
+            *velocity = snapshot.velocity;
+        }
+        if let Some(steering) = steerings.get_mut(self.player) {
+            *steering = snapshot.steering;
+        }
+        true
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b>, StateEvent> for RewindingState {
+    fn update(
+        &mut self,
+        data: StateData<'_, CustomGameData<'_, '_>>,
+    ) -> Trans<CustomGameData<'a, 'b>, StateEvent> {
+        let StateData { world, .. } = data;
+        let seconds_per_rewind_frame = world.fetch::<DebugConfig>().seconds_per_rewind_frame;
+        let raw_delta = world.fetch::<Time>().delta_seconds();
+
+        if !world
+            .fetch::<InputHandler<StringBindings>>()
+            .action_is_down("rewind")
+            .unwrap_or(false)
+        {
+            return Trans::Pop;
+        }
+
+        if seconds_per_rewind_frame <= 0.0 {
+            // With a non-positive interval (DebugConfig's own default is 0.0), subtracting
+            // it never brings `elapsed_since_last_step` back under the threshold, so the
+            // `while` below would keep stepping until the RewindBuffer is empty: it would
+            // drain the entire buffer in a single frame instead of stepping through it.
+            // Fall back to stepping once per real frame instead.
+            self.apply_next_rewind_step(world);
+            return Trans::None;
+        }
+
+        self.elapsed_since_last_step += raw_delta;
+        while self.elapsed_since_last_step >= seconds_per_rewind_frame {
+            self.elapsed_since_last_step -= seconds_per_rewind_frame;
+            if !self.apply_next_rewind_step(world) {
+                break;
+            }
+        }
+        Trans::None
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, CustomGameData<'_, '_>>) {
+        // Re-seed the recorder so it doesn't immediately overwrite the position we just
+        // rewound to; the recorder resumes one full interval from here.
+        let world = data.world;
+        let interval = world.fetch::<DebugConfig>().seconds_per_rewind_frame;
+        let elapsed = world.fetch::<GameClock>().elapsed();
+        world.fetch_mut::<RewindBuffer>().reseed(elapsed, interval);
+    }
+}